@@ -86,10 +86,188 @@
 //!
 //! `binary` is considered as uniquely borrowed if closure is used, then the borrow checker
 //! won't allow us do above things. However, macros can do such things.
+//!
+//! # Thread safety
+//!
+//! [`generate_on_demand_macro`] stores its state in a [`RefCell`][std::cell::RefCell], which is
+//! `!Sync`, so the generated accessors cannot be shared across threads. When the on-demand value
+//! must be reachable from a worker pool (for instance, a parser whose lazily-read fields are
+//! touched from several threads), use [`generate_on_demand_macro_sync`] instead. It generates the
+//! same three accessors, backed by an atomic-flag borrow cell instead of a `RefCell`, so the
+//! storage is `Sync` whenever `T: Send + Sync` (the same bound [`RwLock`][std::sync::RwLock]
+//! requires, since `read()` hands out the same `&T` to any number of threads at once):
+//!
+//! ```rust
+//! use on_demand::generate_on_demand_macro_sync;
+//! use std::sync::atomic::{AtomicUsize, Ordering};
+//!
+//! static CALLS: AtomicUsize = AtomicUsize::new(0);
+//!
+//! fn foo() {
+//!     generate_on_demand_macro_sync!(a: usize = None, {
+//!         CALLS.fetch_add(1, Ordering::SeqCst);
+//!         1
+//!     });
+//!
+//!     std::thread::scope(|scope| {
+//!         for _ in 0..8 {
+//!             scope.spawn(|| {
+//!                 let a_data = on_demand_get_a!();
+//!                 assert_eq!(*a_data, 1);
+//!             });
+//!         }
+//!     });
+//!     assert_eq!(CALLS.load(Ordering::SeqCst), 1);
+//! }
+//! ```
+//!
+//! Just like the `RefCell`-backed cell, two racing readers never see a half-initialized value and
+//! only one of them ever runs the getter expression: the lazy initialization happens while the
+//! cell is exclusively borrowed, so a second thread that is also trying to compute the value
+//! simply waits for the first one to finish, then observes the cached result.
+//!
+//! # Fallible initialization
+//!
+//! Real parsing code often needs the getter expression itself to be fallible (a seek or a read
+//! can fail), and panicking from an `.unwrap()` buried in the block is rarely the right behavior
+//! for a library to impose. [`generate_on_demand_macro_try`] is like [`generate_on_demand_macro`],
+//! except the getter block must evaluate to `Result<T, E>`, and the generated accessors
+//! (`on_demand_try_get_x!`, `on_demand_try_get_x_mut!`, `on_demand_try_into_x!`) return
+//! `Result<_, E>` instead of the bare value:
+//!
+//! ```rust
+//! use on_demand::generate_on_demand_macro_try;
+//! use std::io::{Read, Seek, SeekFrom};
+//!
+//! fn foo(binary: &mut (impl Read + Seek)) -> std::io::Result<u32> {
+//!     generate_on_demand_macro_try!(a: u32 = None, {
+//!         let mut buf = [0; 4];
+//!         binary.seek(SeekFrom::Start(0))?;
+//!         binary.read_exact(&mut buf)?;
+//!         Ok::<_, std::io::Error>(u32::from_be_bytes(buf))
+//!     });
+//!     let a_data = on_demand_try_get_a!()?;
+//!     Ok(*a_data)
+//! }
+//! ```
+//!
+//! If the getter expression returns `Err`, the underlying value is left as `None`, so a later call
+//! can retry instead of being poisoned forever. A successful computation is cached just like the
+//! infallible macro.
+//!
+//! # Non-panicking access
+//!
+//! [`generate_on_demand_macro`] also emits `on_demand_checked_get_x!` and
+//! `on_demand_checked_get_x_mut!`, which use [`try_borrow`][std::cell::RefCell::try_borrow] and
+//! [`try_borrow_mut`][std::cell::RefCell::try_borrow_mut] instead of `borrow`/`borrow_mut`, so a
+//! mis-sequenced access (for example, holding a `get_x` guard while calling `get_x_mut`) returns
+//! [`BorrowConflict`] instead of unwinding:
+//!
+//! ```rust
+//! use on_demand::generate_on_demand_macro;
+//!
+//! fn foo() {
+//!     generate_on_demand_macro!(a: usize = None, { 1 });
+//!     let a_ref = on_demand_checked_get_a!().unwrap();
+//!     assert!(on_demand_checked_get_a_mut!().is_err());
+//!     drop(a_ref);
+//!     assert!(on_demand_checked_get_a_mut!().is_ok());
+//! }
+//! ```
+//!
+//! This is especially valuable once the lazy values form dependency graphs, where an accidental
+//! re-entrant access is easy to introduce by accident.
+//!
+//! # Module-scope generation
+//!
+//! Every form above declares a local `let` binding, so the lazily-computed value and its
+//! accessor macros die at the end of the enclosing function body. [`generate_on_demand_macro`]
+//! also accepts a module-scope form that takes a visibility and the path the storage will live
+//! at, and generates a `static` (backed by the same `Sync` cell as
+//! [`generate_on_demand_macro_sync`]) plus accessor macros at that same visibility; with
+//! `pub(crate)` or `pub`, any function in the crate can call them, while without a visibility
+//! they stay private to the defining module. With `pub`, the macros are further `#[macro_export]`-ed so a
+//! downstream crate can call them too — but `#[macro_export]` resolves `crate::` at the *caller's*
+//! crate root, not the defining one, so the path passed to the `pub` form must be given in a form
+//! that still resolves from a downstream crate, i.e. `::my_crate::state` rather than
+//! `crate::state`:
+//!
+//! ```rust
+//! mod state {
+//!     use on_demand::generate_on_demand_macro;
+//!
+//!     generate_on_demand_macro!(pub x in crate::state: u32 = None, { 1 });
+//!     generate_on_demand_macro!(pub(crate) y in crate::state: u32 = None, {
+//!         let x_data = on_demand_get_x!();
+//!         2 + *x_data
+//!     });
+//! }
+//!
+//! fn foo() {
+//!     let y_data = state::on_demand_get_y!();
+//!     assert_eq!(*y_data, 3);
+//! }
+//!
+//! fn main() {
+//!     foo();
+//! }
+//! ```
+//!
+//! As with the stack-local form, `on_demand_get_x!`, `on_demand_get_x_mut!` and `on_demand_into_x!`
+//! are generated. Since a `static` cannot be consumed, `on_demand_into_x!` takes the computed value
+//! out of the cell under a write lock and leaves it uncomputed behind, so a later call lazily
+//! recomputes it rather than panicking on a moved-out value.
+//!
+//! Unlike `pub` (which is backed by `#[macro_export]` and so is reachable by its bare name
+//! anywhere in the crate once defined), other visibilities only make the macros reachable through
+//! their path, `$path::on_demand_get_x!()`; a getter block that depends on a `pub(crate)` or
+//! private sibling needs to call it by that path rather than by its bare name.
 
 #[doc(hidden)]
 pub use paste;
 
+#[doc(hidden)]
+pub mod sync;
+
+/// Which kind of access a checked on-demand accessor attempted when it hit a [`BorrowConflict`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BorrowKind {
+    /// A shared (read) access, as performed by `on_demand_checked_get_x!`.
+    Shared,
+    /// An exclusive (write) access, as performed by `on_demand_checked_get_x_mut!`.
+    Exclusive,
+}
+
+/// Error returned by the `on_demand_checked_get_x!` family of macros when the underlying value is
+/// already borrowed incompatibly with the requested access.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BorrowConflict {
+    attempted: BorrowKind,
+}
+
+impl BorrowConflict {
+    #[doc(hidden)]
+    pub fn new(attempted: BorrowKind) -> Self {
+        Self { attempted }
+    }
+
+    /// The kind of access that could not be completed.
+    pub fn attempted(&self) -> BorrowKind {
+        self.attempted
+    }
+}
+
+impl ::std::fmt::Display for BorrowConflict {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        match self.attempted {
+            BorrowKind::Shared => write!(f, "already mutably borrowed"),
+            BorrowKind::Exclusive => write!(f, "already borrowed"),
+        }
+    }
+}
+
+impl ::std::error::Error for BorrowConflict {}
+
 /// Macro to generate on-demand macro
 #[macro_export]
 macro_rules! generate_on_demand_macro {
@@ -139,10 +317,349 @@ macro_rules! generate_on_demand_macro {
                     }
                 }};
             }
+            #[allow(unused_macros)]
+            macro_rules! [<on_demand_checked_get_ $var>] {
+                () => {{
+                    let init_result = match $var.try_borrow() {
+                        ::std::result::Result::Ok(guard) if guard.is_some() => {
+                            ::std::result::Result::Ok(())
+                        }
+                        ::std::result::Result::Ok(guard) => {
+                            ::std::mem::drop(guard);
+                            match $var.try_borrow_mut() {
+                                ::std::result::Result::Ok(mut guard) => {
+                                    if guard.is_none() {
+                                        *guard = Some({$getter});
+                                    }
+                                    ::std::result::Result::Ok(())
+                                }
+                                ::std::result::Result::Err(_) => ::std::result::Result::Err(
+                                    $crate::BorrowConflict::new($crate::BorrowKind::Exclusive),
+                                ),
+                            }
+                        }
+                        ::std::result::Result::Err(_) => ::std::result::Result::Err(
+                            $crate::BorrowConflict::new($crate::BorrowKind::Shared),
+                        ),
+                    };
+                    init_result.and_then(|()| {
+                        $var.try_borrow()
+                            .map(|guard| {
+                                ::std::cell::Ref::map(guard, |var| {
+                                    if let Some(data) = var.as_ref() {
+                                        data
+                                    } else {
+                                        unreachable!()
+                                    }
+                                })
+                            })
+                            .map_err(|_| $crate::BorrowConflict::new($crate::BorrowKind::Shared))
+                    })
+                }};
+            }
+            #[allow(unused_macros)]
+            macro_rules! [<on_demand_checked_get_ $var _mut>] {
+                () => {{
+                    match $var.try_borrow_mut() {
+                        ::std::result::Result::Ok(mut guard) => {
+                            if guard.is_none() {
+                                *guard = Some({$getter});
+                            }
+                            ::std::result::Result::Ok(::std::cell::RefMut::map(guard, |var| {
+                                if let Some(data) = var.as_mut() {
+                                    data
+                                } else {
+                                    unreachable!()
+                                }
+                            }))
+                        }
+                        ::std::result::Result::Err(_) => ::std::result::Result::Err(
+                            $crate::BorrowConflict::new($crate::BorrowKind::Exclusive),
+                        ),
+                    }
+                }};
+            }
+        }
+    };
+    (pub $var: ident in $path: path : $Inner: ty = $default_value: expr, $getter: expr) => {
+        $crate::paste::paste! {
+            #[doc(hidden)]
+            pub static [<$var:upper _ON_DEMAND_CELL>]: $crate::sync::OnDemandCell<$Inner> =
+                $crate::sync::OnDemandCell::new($default_value);
+
+            // `#[macro_export]` makes these usable from any downstream crate via `$crate::..!`,
+            // which is the only way to give a `macro_rules!` item crate-wide-and-beyond visibility.
+            #[macro_export]
+            macro_rules! [<on_demand_get_ $var>] {
+                () => {{
+                    // Check through a shared borrow first so a thread holding another `read()`
+                    // guard of `$var` never has to wait on `write()` just to find the value is
+                    // already computed; only an uninitialized cell escalates to the exclusive lock.
+                    if $path::[<$var:upper _ON_DEMAND_CELL>].read().is_none() {
+                        let mut guard = $path::[<$var:upper _ON_DEMAND_CELL>].write();
+                        if guard.is_none() {
+                            *guard = Some({$getter});
+                        }
+                    }
+                    $crate::sync::OnDemandRef::map($path::[<$var:upper _ON_DEMAND_CELL>].read(), |var| {
+                        if let Some(data) = var.as_ref() {
+                            data
+                        } else {
+                            unreachable!()
+                        }
+                    })
+                }};
+            }
+            #[macro_export]
+            macro_rules! [<on_demand_get_ $var _mut>] {
+                () => {{
+                    let mut guard = $path::[<$var:upper _ON_DEMAND_CELL>].write();
+                    if guard.is_none() {
+                        *guard = Some({$getter});
+                    }
+                    $crate::sync::OnDemandRefMut::map(guard, |var| {
+                        if let Some(data) = var.as_mut() {
+                            data
+                        } else {
+                            unreachable!()
+                        }
+                    })
+                }};
+            }
+            #[macro_export]
+            macro_rules! [<on_demand_into_ $var>] {
+                () => {{
+                    let mut guard = $path::[<$var:upper _ON_DEMAND_CELL>].write();
+                    if guard.is_none() {
+                        *guard = Some({$getter});
+                    }
+                    guard.take().unwrap()
+                }};
+            }
+        }
+    };
+    ($vis: vis $var: ident in $path: path : $Inner: ty = $default_value: expr, $getter: expr) => {
+        $crate::paste::paste! {
+            #[doc(hidden)]
+            $vis static [<$var:upper _ON_DEMAND_CELL>]: $crate::sync::OnDemandCell<$Inner> =
+                $crate::sync::OnDemandCell::new($default_value);
+
+            // `macro_rules!` items don't accept a visibility keyword directly, so the macro is
+            // declared private (textually scoped to this module) and then re-exported at the
+            // requested visibility via `use`, which is the standard path-based macro idiom.
+            #[allow(unused_macros)]
+            macro_rules! [<on_demand_get_ $var>] {
+                () => {{
+                    // Check through a shared borrow first so a thread holding another `read()`
+                    // guard of `$var` never has to wait on `write()` just to find the value is
+                    // already computed; only an uninitialized cell escalates to the exclusive lock.
+                    if $path::[<$var:upper _ON_DEMAND_CELL>].read().is_none() {
+                        let mut guard = $path::[<$var:upper _ON_DEMAND_CELL>].write();
+                        if guard.is_none() {
+                            *guard = Some({$getter});
+                        }
+                    }
+                    $crate::sync::OnDemandRef::map($path::[<$var:upper _ON_DEMAND_CELL>].read(), |var| {
+                        if let Some(data) = var.as_ref() {
+                            data
+                        } else {
+                            unreachable!()
+                        }
+                    })
+                }};
+            }
+            #[allow(unused_macros)]
+            macro_rules! [<on_demand_get_ $var _mut>] {
+                () => {{
+                    let mut guard = $path::[<$var:upper _ON_DEMAND_CELL>].write();
+                    if guard.is_none() {
+                        *guard = Some({$getter});
+                    }
+                    $crate::sync::OnDemandRefMut::map(guard, |var| {
+                        if let Some(data) = var.as_mut() {
+                            data
+                        } else {
+                            unreachable!()
+                        }
+                    })
+                }};
+            }
+            #[allow(unused_macros)]
+            macro_rules! [<on_demand_into_ $var>] {
+                () => {{
+                    let mut guard = $path::[<$var:upper _ON_DEMAND_CELL>].write();
+                    if guard.is_none() {
+                        *guard = Some({$getter});
+                    }
+                    guard.take().unwrap()
+                }};
+            }
+            #[allow(unused_imports)]
+            $vis use [<on_demand_get_ $var>];
+            #[allow(unused_imports)]
+            $vis use [<on_demand_get_ $var _mut>];
+            #[allow(unused_imports)]
+            $vis use [<on_demand_into_ $var>];
+        }
+    };
+}
+
+/// Macro to generate on-demand macro, backed by a `Sync` borrow cell so the generated accessors
+/// can be shared across threads. See the [crate-level thread safety notes](crate#thread-safety)
+/// for an example.
+#[macro_export]
+macro_rules! generate_on_demand_macro_sync {
+    ($var: ident: $Inner: ty = $default_value: expr, $getter: expr) => {
+        let $var: $crate::sync::OnDemandCell<$Inner> = $crate::sync::OnDemandCell::new($default_value);
+        $crate::paste::paste! {
+            #[allow(unused_macros)]
+            macro_rules! [<on_demand_get_ $var>] {
+                () => {{
+                    // Check through a shared borrow first so a thread holding another `read()`
+                    // guard of `$var` never has to wait on `write()` just to find the value is
+                    // already computed; only an uninitialized cell escalates to the exclusive lock.
+                    if $var.read().is_none() {
+                        let mut guard = $var.write();
+                        if guard.is_none() {
+                            *guard = Some({$getter});
+                        }
+                    }
+                    $crate::sync::OnDemandRef::map($var.read(), |var| {
+                        if let Some(data) = var.as_ref() {
+                            data
+                        } else {
+                            unreachable!()
+                        }
+                    })
+                }};
+            }
+            #[allow(unused_macros)]
+            macro_rules! [<on_demand_get_ $var _mut>] {
+                () => {{
+                    let mut guard = $var.write();
+                    if guard.is_none() {
+                        *guard = Some({$getter});
+                    }
+                    $crate::sync::OnDemandRefMut::map(guard, |var| {
+                        if let Some(data) = var.as_mut() {
+                            data
+                        } else {
+                            unreachable!()
+                        }
+                    })
+                }};
+            }
+            #[allow(unused_macros)]
+            macro_rules! [<on_demand_into_ $var>] {
+                () => {{
+                    {
+                        let mut guard = $var.write();
+                        if guard.is_none() {
+                            *guard = Some({$getter});
+                        }
+                    }
+                    if let Some(data) = $var.into_inner() {
+                        data
+                    } else {
+                        unreachable!()
+                    }
+                }};
+            }
         }
     };
 }
 
+/// Macro to generate on-demand macro whose getter expression is fallible. See the
+/// [crate-level fallible initialization notes](crate#fallible-initialization) for an example.
+#[macro_export]
+macro_rules! generate_on_demand_macro_try {
+    ($var: ident: $Inner: ty = $default_value: expr, $getter: expr) => {
+        let $var: ::std::cell::RefCell<::std::option::Option<$Inner>> =
+            ::std::cell::RefCell::new($default_value);
+        $crate::paste::paste! {
+            #[allow(unused_macros)]
+            macro_rules! [<on_demand_try_get_ $var>] {
+                () => {{
+                    let is_some = $var.borrow().is_some();
+                    let init_result = if is_some {
+                        ::std::result::Result::Ok(())
+                    } else {
+                        match {$getter} {
+                            ::std::result::Result::Ok(value) => {
+                                *($var.borrow_mut()) = Some(value);
+                                ::std::result::Result::Ok(())
+                            }
+                            ::std::result::Result::Err(e) => ::std::result::Result::Err(e),
+                        }
+                    };
+                    match init_result {
+                        ::std::result::Result::Ok(()) => ::std::result::Result::Ok(
+                            ::std::cell::Ref::map($var.borrow(), |var| {
+                                if let Some(data) = var.as_ref() {
+                                    data
+                                } else {
+                                    unreachable!()
+                                }
+                            }),
+                        ),
+                        ::std::result::Result::Err(e) => ::std::result::Result::Err(e),
+                    }
+                }};
+            }
+            #[allow(unused_macros)]
+            macro_rules! [<on_demand_try_get_ $var _mut>] {
+                () => {{
+                    let is_some = $var.borrow().is_some();
+                    let init_result = if is_some {
+                        ::std::result::Result::Ok(())
+                    } else {
+                        match {$getter} {
+                            ::std::result::Result::Ok(value) => {
+                                *($var.borrow_mut()) = Some(value);
+                                ::std::result::Result::Ok(())
+                            }
+                            ::std::result::Result::Err(e) => ::std::result::Result::Err(e),
+                        }
+                    };
+                    match init_result {
+                        ::std::result::Result::Ok(()) => ::std::result::Result::Ok(
+                            ::std::cell::RefMut::map($var.borrow_mut(), |var| {
+                                if let Some(data) = var.as_mut() {
+                                    data
+                                } else {
+                                    unreachable!()
+                                }
+                            }),
+                        ),
+                        ::std::result::Result::Err(e) => ::std::result::Result::Err(e),
+                    }
+                }};
+            }
+            #[allow(unused_macros)]
+            macro_rules! [<on_demand_try_into_ $var>] {
+                () => {{
+                    let taken = $var.into_inner();
+                    match taken {
+                        ::std::option::Option::Some(data) => ::std::result::Result::Ok(data),
+                        ::std::option::Option::None => {$getter}
+                    }
+                }};
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod module_scope_fixture {
+    use crate::generate_on_demand_macro;
+
+    generate_on_demand_macro!(pub(crate) x in crate::module_scope_fixture: u32 = None, { 1 });
+    generate_on_demand_macro!(pub(crate) y in crate::module_scope_fixture: u32 = None, {
+        let x_data = crate::module_scope_fixture::on_demand_get_x!();
+        2 + *x_data
+    });
+}
+
 #[cfg(test)]
 mod tests {
     use super::generate_on_demand_macro;
@@ -180,4 +697,124 @@ mod tests {
         let c_data = on_demand_into_c!();
         assert_eq!(c_data, 0xc080400);
     }
+
+    #[test]
+    fn test_sync_reader() {
+        use super::generate_on_demand_macro_sync;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+        generate_on_demand_macro_sync!(a: usize = None, {
+            CALLS.fetch_add(1, Ordering::SeqCst);
+            1
+        });
+        generate_on_demand_macro_sync!(b: usize = None, {
+            let a_data = on_demand_get_a!();
+            2 + *a_data
+        });
+
+        std::thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| {
+                    let a_data = on_demand_get_a!();
+                    assert_eq!(*a_data, 1);
+                });
+            }
+        });
+        assert_eq!(CALLS.load(Ordering::SeqCst), 1);
+
+        // Two overlapping shared reads of the same var must not deadlock.
+        let g1 = on_demand_get_a!();
+        let g2 = on_demand_get_a!();
+        assert_eq!(*g1, 1);
+        assert_eq!(*g2, 1);
+        drop(g2);
+
+        // Holding a read guard of `a` while computing `b`, whose getter itself reads `a`,
+        // must not deadlock either.
+        let b_data = on_demand_get_b!();
+        assert_eq!(*b_data, 3);
+        drop(b_data);
+        drop(g1);
+
+        let mut b_data = on_demand_get_b_mut!();
+        assert_eq!(*b_data, 3);
+        *b_data = 42;
+        drop(b_data);
+        let b_owned = on_demand_into_b!();
+        assert_eq!(b_owned, 42);
+    }
+
+    #[test]
+    fn test_try_reader() {
+        use super::generate_on_demand_macro_try;
+        use std::cell::Cell;
+
+        let attempts = Cell::new(0);
+        generate_on_demand_macro_try!(a: u32 = None, {
+            attempts.set(attempts.get() + 1);
+            if attempts.get() < 2 {
+                Err("not ready yet")
+            } else {
+                Ok(7)
+            }
+        });
+
+        let first: Result<_, &str> = on_demand_try_get_a!();
+        assert_eq!(first.err(), Some("not ready yet"));
+
+        let second = on_demand_try_get_a!();
+        assert_eq!(*second.unwrap(), 7);
+        assert_eq!(attempts.get(), 2);
+
+        generate_on_demand_macro_try!(b: u32 = None, {
+            match on_demand_try_get_a!() {
+                Ok(a_data) => Ok(3 + *a_data),
+                Err(e) => Err(e),
+            }
+        });
+        let b_data = on_demand_try_get_b_mut!().unwrap();
+        assert_eq!(*b_data, 10);
+        drop(b_data);
+        let b_owned = on_demand_try_into_b!().unwrap();
+        assert_eq!(b_owned, 10);
+    }
+
+    #[test]
+    fn test_checked_reader() {
+        use super::{generate_on_demand_macro, BorrowKind};
+
+        generate_on_demand_macro!(a: usize = None, { 1 });
+
+        let a_ref = on_demand_checked_get_a!().unwrap();
+        assert_eq!(*a_ref, 1);
+
+        let conflict = on_demand_checked_get_a_mut!().unwrap_err();
+        assert_eq!(conflict.attempted(), BorrowKind::Exclusive);
+
+        drop(a_ref);
+
+        let mut a_mut = on_demand_checked_get_a_mut!().unwrap();
+        *a_mut = 2;
+
+        let conflict = on_demand_checked_get_a!().unwrap_err();
+        assert_eq!(conflict.attempted(), BorrowKind::Shared);
+
+        drop(a_mut);
+        assert_eq!(*on_demand_checked_get_a!().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_module_scope() {
+        let y_data = crate::module_scope_fixture::on_demand_get_y!();
+        assert_eq!(*y_data, 3);
+
+        let mut x_mut = crate::module_scope_fixture::on_demand_get_x_mut!();
+        *x_mut = 10;
+        drop(x_mut);
+
+        let x_owned = crate::module_scope_fixture::on_demand_into_x!();
+        assert_eq!(x_owned, 10);
+    }
 }
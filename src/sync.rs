@@ -0,0 +1,182 @@
+//! A small `Sync` borrow cell used by [`generate_on_demand_macro_sync`][crate::generate_on_demand_macro_sync].
+//!
+//! This is an internal implementation detail of the crate, exposed only so the expanded macro
+//! code can name its types; it is not meant to be used directly.
+
+use std::cell::UnsafeCell;
+use std::hint;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Flag value meaning the cell is exclusively (mutably) borrowed.
+const EXCLUSIVE: usize = usize::MAX;
+
+/// A `TrustCell`-style container: an [`UnsafeCell`] guarded by an [`AtomicUsize`] borrow flag.
+///
+/// The flag is `0` when free, `EXCLUSIVE` while a write borrow is in progress, and any other
+/// value `n` while `n` read borrows are in progress. Unlike [`RefCell`][std::cell::RefCell], this
+/// makes the cell `Sync` whenever `T: Send + Sync`, the same bound [`RwLock`][std::sync::RwLock]
+/// requires: `read()` can hand out the same `&T` to any number of threads at once, so `T` itself
+/// must tolerate shared cross-thread access.
+#[doc(hidden)]
+pub struct OnDemandCell<T> {
+    value: UnsafeCell<Option<T>>,
+    flag: AtomicUsize,
+}
+
+unsafe impl<T: Send + Sync> Sync for OnDemandCell<T> {}
+
+impl<T> OnDemandCell<T> {
+    /// Creates a new cell holding `value`.
+    ///
+    /// This is a `const fn` so the cell can back a `static`, which is what module-scope on-demand
+    /// storage (see [`generate_on_demand_macro`][crate::generate_on_demand_macro]) needs.
+    pub const fn new(value: Option<T>) -> Self {
+        Self {
+            value: UnsafeCell::new(value),
+            flag: AtomicUsize::new(0),
+        }
+    }
+
+    /// Acquires a shared borrow, spinning while the cell is exclusively borrowed by another
+    /// thread. Never panics: any number of shared borrows may coexist, so this only ever waits
+    /// out contention with a [`write`][Self::write] borrow, not with other `read` borrows.
+    ///
+    /// Unlike [`RefCell::borrow`][std::cell::RefCell::borrow], this blocks instead of panicking
+    /// on conflict, which means holding a `read()` guard and then calling [`write`][Self::write]
+    /// (or holding a `write()` guard and then calling `read()`) on the *same thread* spins
+    /// forever rather than aborting — there is no thread-local owner to detect the reentrancy.
+    pub fn read(&self) -> OnDemandRef<'_, Option<T>> {
+        loop {
+            let current = self.flag.load(Ordering::Acquire);
+            if current == EXCLUSIVE {
+                hint::spin_loop();
+                continue;
+            }
+            if self
+                .flag
+                .compare_exchange_weak(
+                    current,
+                    current + 1,
+                    Ordering::Acquire,
+                    Ordering::Relaxed,
+                )
+                .is_ok()
+            {
+                break;
+            }
+        }
+        OnDemandRef {
+            value: unsafe { &*self.value.get() },
+            flag: &self.flag,
+        }
+    }
+
+    /// Acquires the exclusive borrow, spinning until the flag is `0` and then setting it to
+    /// `EXCLUSIVE`.
+    ///
+    /// Blocks rather than panicking on conflict, so calling this while the current thread
+    /// already holds a `read()` or `write()` guard of the same cell deadlocks instead of
+    /// aborting — see the reentrancy note on [`read`][Self::read].
+    pub fn write(&self) -> OnDemandRefMut<'_, Option<T>> {
+        loop {
+            if self
+                .flag
+                .compare_exchange_weak(0, EXCLUSIVE, Ordering::Acquire, Ordering::Relaxed)
+                .is_ok()
+            {
+                break;
+            }
+            hint::spin_loop();
+        }
+        OnDemandRefMut {
+            value: unsafe { &mut *self.value.get() },
+            flag: &self.flag,
+        }
+    }
+
+    /// Consumes the cell, returning the wrapped value. Since this takes `self` by value, no
+    /// borrow bookkeeping is needed.
+    pub fn into_inner(self) -> Option<T> {
+        self.value.into_inner()
+    }
+}
+
+/// A shared borrow of an [`OnDemandCell`], analogous to [`std::cell::Ref`].
+///
+/// Decrements the cell's borrow flag on [`Drop`].
+#[doc(hidden)]
+pub struct OnDemandRef<'a, T: ?Sized> {
+    value: &'a T,
+    flag: &'a AtomicUsize,
+}
+
+impl<'a, T: ?Sized> OnDemandRef<'a, T> {
+    /// Makes a new `OnDemandRef` for a component of the borrowed data, analogous to
+    /// [`std::cell::Ref::map`].
+    pub fn map<U: ?Sized>(orig: Self, f: impl FnOnce(&T) -> &U) -> OnDemandRef<'a, U> {
+        let mapped = OnDemandRef {
+            value: f(orig.value),
+            flag: orig.flag,
+        };
+        std::mem::forget(orig);
+        mapped
+    }
+}
+
+impl<'a, T: ?Sized> Deref for OnDemandRef<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value
+    }
+}
+
+impl<'a, T: ?Sized> Drop for OnDemandRef<'a, T> {
+    fn drop(&mut self) {
+        self.flag.fetch_sub(1, Ordering::Release);
+    }
+}
+
+/// An exclusive borrow of an [`OnDemandCell`], analogous to [`std::cell::RefMut`].
+///
+/// Resets the cell's borrow flag to `0` on [`Drop`].
+#[doc(hidden)]
+pub struct OnDemandRefMut<'a, T: ?Sized> {
+    value: &'a mut T,
+    flag: &'a AtomicUsize,
+}
+
+impl<'a, T: ?Sized> OnDemandRefMut<'a, T> {
+    /// Makes a new `OnDemandRefMut` for a component of the borrowed data, analogous to
+    /// [`std::cell::RefMut::map`].
+    pub fn map<U: ?Sized>(orig: Self, f: impl FnOnce(&mut T) -> &mut U) -> OnDemandRefMut<'a, U> {
+        let flag = orig.flag;
+        let value = unsafe { &mut *(orig.value as *mut T) };
+        std::mem::forget(orig);
+        OnDemandRefMut {
+            value: f(value),
+            flag,
+        }
+    }
+}
+
+impl<'a, T: ?Sized> Deref for OnDemandRefMut<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value
+    }
+}
+
+impl<'a, T: ?Sized> DerefMut for OnDemandRefMut<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.value
+    }
+}
+
+impl<'a, T: ?Sized> Drop for OnDemandRefMut<'a, T> {
+    fn drop(&mut self) {
+        self.flag.store(0, Ordering::Release);
+    }
+}